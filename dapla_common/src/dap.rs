@@ -5,7 +5,7 @@ pub use self::{access::*, settings::*};
 pub mod access;
 pub mod settings;
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Dap<P> {
     name: String,
     root_dir: P,
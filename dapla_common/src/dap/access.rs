@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+/// A capability a dap can request and, if granted, rely on at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    /// Outgoing HTTP requests.
+    Http,
+    /// Reading files under the dap's own `root_dir`.
+    FileRead,
+    /// Writing files under the dap's own `root_dir`.
+    FileWrite,
+    /// Raw TCP sockets.
+    Tcp,
+    /// WebSocket connections.
+    Websocket,
+}
@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+use super::Permission;
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct DapSettings {
+    #[serde(default)]
+    pub application: ApplicationSettings,
+    #[serde(default)]
+    pub permissions: PermissionsSettings,
+    #[serde(default)]
+    pub embedding: EmbeddingSettings,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct ApplicationSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub title: String,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct PermissionsSettings {
+    #[serde(default)]
+    pub required: Vec<Permission>,
+    #[serde(default)]
+    pub allowed: Vec<Permission>,
+}
+
+/// How a dap may be framed by other origins. Defaults to denying framing entirely.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct EmbeddingSettings {
+    /// Whether this dap may be embedded in an `<iframe>` at all.
+    #[serde(default)]
+    pub allow_embedding: bool,
+    /// Origins allowed as `frame-ancestors` when `allow_embedding` is set.
+    #[serde(default)]
+    pub allowed_frame_ancestors: Vec<String>,
+    /// Extra CSP directives to append, e.g. restricting where the dap may load content from.
+    #[serde(default)]
+    pub content_security_policy: Option<String>,
+}
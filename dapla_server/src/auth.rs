@@ -0,0 +1,249 @@
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use actix_web::{cookie::Cookie, dev::Payload, web, FromRequest, HttpRequest, HttpResponse};
+use ed25519_dalek::{PublicKey, Signature, Verifier};
+use futures::future::{ready, Ready};
+use hmac::{Hmac, Mac, NewMac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use thiserror::Error;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const COOKIE_NAME: &str = "dapla_admin";
+const CHALLENGE_TTL: Duration = Duration::from_secs(60);
+const SESSION_TTL_SECS: u64 = 60 * 60 * 24;
+
+/// Outstanding login challenges, keyed by the session id handed out alongside the nonce.
+#[derive(Default)]
+pub struct AuthService {
+    challenges: Mutex<HashMap<String, (Vec<u8>, Instant)>>,
+}
+
+impl AuthService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn issue_challenge(&self) -> ChallengeResponse {
+        let mut nonce = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut nonce);
+
+        let session_id = bs58::encode(&nonce[..16]).into_string();
+        self.challenges
+            .lock()
+            .expect("Challenges lock should be acquired")
+            .insert(session_id.clone(), (nonce.to_vec(), Instant::now() + CHALLENGE_TTL));
+
+        ChallengeResponse {
+            session_id,
+            nonce: base64::encode(nonce),
+        }
+    }
+
+    fn take_challenge(&self, session_id: &str) -> Option<Vec<u8>> {
+        let mut challenges = self.challenges.lock().expect("Challenges lock should be acquired");
+        match challenges.remove(session_id) {
+            Some((nonce, expires_at)) if expires_at > Instant::now() => Some(nonce),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ChallengeResponse {
+    session_id: String,
+    nonce: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LoginRequest {
+    session_id: String,
+    public_key: String,
+    signature: String,
+}
+
+pub async fn challenge(auth_service: web::Data<AuthService>) -> HttpResponse {
+    HttpResponse::Ok().json(auth_service.issue_challenge())
+}
+
+/// Base58-encoded ed25519 public keys allowed to log in as admin. A newtype over `Vec<String>`
+/// so it doesn't collide with `install`'s `trusted_publisher_keys` app data, which is the same
+/// underlying type.
+pub struct AdminPublicKeys(pub Vec<String>);
+
+pub async fn login(
+    auth_service: web::Data<AuthService>,
+    server_secret: web::Data<String>,
+    admin_public_keys: web::Data<AdminPublicKeys>,
+    body: String,
+) -> HttpResponse {
+    let request: LoginRequest = match serde_json::from_str(&body) {
+        Ok(request) => request,
+        Err(err) => return HttpResponse::BadRequest().body(err.to_string()),
+    };
+
+    let nonce = match auth_service.take_challenge(&request.session_id) {
+        Some(nonce) => nonce,
+        None => return HttpResponse::Unauthorized().body(AuthError::MissingSession.to_string()),
+    };
+
+    match verify_login(&request, &nonce, &admin_public_keys.0)
+        .map(|public_key_bytes| session_cookie_value(&public_key_bytes, server_secret.as_bytes()))
+    {
+        Ok(cookie_value) => {
+            let cookie = Cookie::build(COOKIE_NAME, cookie_value).path("/").http_only(true).finish();
+            HttpResponse::Ok().cookie(cookie).finish()
+        }
+        Err(err) => HttpResponse::Unauthorized().body(err.to_string()),
+    }
+}
+
+fn verify_login(request: &LoginRequest, nonce: &[u8], admin_public_keys: &[String]) -> AuthResult<Vec<u8>> {
+    let public_key_bytes = bs58::decode(&request.public_key).into_vec().map_err(|_| AuthError::InvalidPublicKey)?;
+    let public_key = PublicKey::from_bytes(&public_key_bytes).map_err(|_| AuthError::InvalidPublicKey)?;
+    let signature_bytes = bs58::decode(&request.signature).into_vec().map_err(|_| AuthError::InvalidSignature)?;
+    let signature = Signature::from_bytes(&signature_bytes).map_err(|_| AuthError::InvalidSignature)?;
+
+    public_key.verify(nonce, &signature).map_err(|_| AuthError::InvalidSignature)?;
+
+    if !admin_public_keys.iter().any(|key| key == &request.public_key) {
+        return Err(AuthError::UntrustedPublicKey);
+    }
+    Ok(public_key_bytes)
+}
+
+fn session_cookie_value(public_key_bytes: &[u8], server_secret: &[u8]) -> String {
+    let expiry = now_unix() + SESSION_TTL_SECS;
+
+    let mut payload = public_key_bytes.to_vec();
+    payload.extend_from_slice(&expiry.to_be_bytes());
+
+    let mut mac = HmacSha256::new_from_slice(server_secret).expect("HMAC accepts any key length");
+    mac.update(&payload);
+    payload.extend_from_slice(&mac.finalize().into_bytes());
+
+    base64::encode(payload)
+}
+
+fn verify_session_cookie(value: &str, server_secret: &[u8]) -> AuthResult<AdminRights> {
+    let payload = base64::decode(value).map_err(|_| AuthError::InvalidSession)?;
+    if payload.len() < 8 + 32 {
+        return Err(AuthError::InvalidSession);
+    }
+
+    let (signed, tag) = payload.split_at(payload.len() - 32);
+    let (public_key_bytes, expiry_bytes) = signed.split_at(signed.len() - 8);
+
+    let mut mac = HmacSha256::new_from_slice(server_secret).expect("HMAC accepts any key length");
+    mac.update(signed);
+    mac.verify(tag).map_err(|_| AuthError::InvalidSession)?;
+
+    let expiry = u64::from_be_bytes(expiry_bytes.try_into().expect("Expiry is 8 bytes"));
+    if expiry < now_unix() {
+        return Err(AuthError::SessionExpired);
+    }
+
+    Ok(AdminRights {
+        public_key: public_key_bytes.to_vec(),
+    })
+}
+
+fn now_unix() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("System clock should be after the epoch")
+        .as_secs()
+}
+
+/// Extractor proving the request carries a session cookie issued by a successful [`login`].
+pub struct AdminRights {
+    pub public_key: Vec<u8>,
+}
+
+impl FromRequest for AdminRights {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+    type Config = ();
+
+    fn from_request(request: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let server_secret = request
+            .app_data::<web::Data<String>>()
+            .expect("Server secret should be configured as app data");
+
+        let result = request
+            .cookie(COOKIE_NAME)
+            .ok_or(AuthError::MissingSession)
+            .and_then(|cookie| verify_session_cookie(cookie.value(), server_secret.as_bytes()));
+
+        ready(result.map_err(|err| actix_web::error::ErrorUnauthorized(err.to_string())))
+    }
+}
+
+pub type AuthResult<T> = Result<T, AuthError>;
+
+#[derive(Debug, Error)]
+pub enum AuthError {
+    #[error("Invalid public key")]
+    InvalidPublicKey,
+
+    #[error("Invalid signature")]
+    InvalidSignature,
+
+    #[error("Public key is not a trusted admin")]
+    UntrustedPublicKey,
+
+    #[error("Challenge is unknown or has expired, request a new one")]
+    MissingSession,
+
+    #[error("Admin session cookie is malformed")]
+    InvalidSession,
+
+    #[error("Admin session has expired, log in again")]
+    SessionExpired,
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::{Keypair, Signer};
+    use rand::rngs::OsRng;
+
+    use super::*;
+
+    #[test]
+    fn verify_login_rejects_a_key_outside_the_allowlist() {
+        let keypair = Keypair::generate(&mut OsRng);
+        let nonce = b"some-challenge-nonce";
+        let request = LoginRequest {
+            session_id: "session".to_string(),
+            public_key: bs58::encode(keypair.public.as_bytes()).into_string(),
+            signature: bs58::encode(keypair.sign(nonce).to_bytes()).into_string(),
+        };
+
+        // A self-generated keypair never appears in the admin allowlist, even though its
+        // signature over the nonce is perfectly valid.
+        let result = verify_login(&request, nonce, &[]);
+        assert!(matches!(result, Err(AuthError::UntrustedPublicKey)));
+    }
+
+    #[test]
+    fn verify_login_accepts_an_allowlisted_key() {
+        let keypair = Keypair::generate(&mut OsRng);
+        let nonce = b"some-challenge-nonce";
+        let encoded_public_key = bs58::encode(keypair.public.as_bytes()).into_string();
+        let request = LoginRequest {
+            session_id: "session".to_string(),
+            public_key: encoded_public_key.clone(),
+            signature: bs58::encode(keypair.sign(nonce).to_bytes()).into_string(),
+        };
+
+        let result = verify_login(&request, nonce, &[encoded_public_key]);
+        assert!(result.is_ok());
+    }
+}
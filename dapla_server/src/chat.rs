@@ -0,0 +1,234 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use actix::{Actor, ActorContext, Addr, AsyncContext, Handler, Message, Running, StreamHandler};
+use actix_web::{web, Error, HttpRequest, HttpResponse};
+use actix_web_actors::ws;
+use log::error;
+use serde::{Deserialize, Serialize};
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Relays opaque, already-encrypted frames between connected chat peers, keyed by `PeerId`.
+/// The server never sees plaintext: it only knows which peer a frame is addressed to.
+///
+/// Each registration is tagged with the owning session's `session_token`, so a session can only
+/// ever unregister its own slot, and a still-connected peer's slot can't be taken over by another
+/// session simply asserting the same `peer_id`.
+#[derive(Default)]
+pub struct ChatRelay {
+    peers: Mutex<HashMap<String, (u64, Addr<ChatSession>)>>,
+}
+
+impl ChatRelay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `addr` under `peer_id`, unless that `peer_id` is already held by a still
+    /// connected session (in which case the caller should refuse the connection).
+    fn register(&self, peer_id: String, session_token: u64, addr: Addr<ChatSession>) -> bool {
+        let mut peers = self.peers.lock().expect("Chat relay lock should be acquired");
+        if let Some((_, existing)) = peers.get(&peer_id) {
+            if existing.connected() {
+                return false;
+            }
+        }
+        peers.insert(peer_id, (session_token, addr));
+        true
+    }
+
+    /// Removes the `peer_id` registration, but only if it's still owned by `session_token`, so a
+    /// stale/reconnecting session can't evict a newer registration for the same `peer_id`.
+    fn unregister(&self, peer_id: &str, session_token: u64) {
+        let mut peers = self.peers.lock().expect("Chat relay lock should be acquired");
+        if matches!(peers.get(peer_id), Some((token, _)) if *token == session_token) {
+            peers.remove(peer_id);
+        }
+    }
+
+    fn recipient(&self, peer_id: &str) -> Option<Addr<ChatSession>> {
+        self.peers
+            .lock()
+            .expect("Chat relay lock should be acquired")
+            .get(peer_id)
+            .map(|(_, addr)| addr.clone())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Message)]
+#[rtype(result = "()")]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum RelayFrame {
+    /// Sent once right after connecting, to tell the relay which peer id this socket is for.
+    Register { peer_id: String },
+    /// An opaque, E2E-encrypted frame forwarded as-is to `to`.
+    Message { to: String, from: String, ciphertext: String },
+}
+
+pub struct ChatSession {
+    peer_id: Option<String>,
+    /// Identifies this session uniquely among all `ChatSession`s, so `ChatRelay` can tell two
+    /// sessions registering the same `peer_id` apart without relying on `Addr` equality.
+    session_token: u64,
+    relay: web::Data<ChatRelay>,
+    last_heartbeat: Instant,
+}
+
+impl ChatSession {
+    fn new(relay: web::Data<ChatRelay>) -> Self {
+        Self {
+            peer_id: None,
+            session_token: rand::random(),
+            relay,
+            last_heartbeat: Instant::now(),
+        }
+    }
+
+    fn heartbeat(&self, ctx: &mut ws::WebsocketContext<Self>) {
+        ctx.run_interval(HEARTBEAT_INTERVAL, |session, ctx| {
+            if Instant::now().duration_since(session.last_heartbeat) > CLIENT_TIMEOUT {
+                if let Some(peer_id) = &session.peer_id {
+                    session.relay.unregister(peer_id, session.session_token);
+                }
+                ctx.stop();
+                return;
+            }
+            ctx.ping(b"");
+        });
+    }
+}
+
+impl Actor for ChatSession {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.heartbeat(ctx);
+    }
+
+    fn stopping(&mut self, _ctx: &mut Self::Context) -> Running {
+        if let Some(peer_id) = &self.peer_id {
+            self.relay.unregister(peer_id, self.session_token);
+        }
+        Running::Stop
+    }
+}
+
+impl Handler<RelayFrame> for ChatSession {
+    type Result = ();
+
+    fn handle(&mut self, frame: RelayFrame, ctx: &mut Self::Context) {
+        if let Ok(text) = serde_json::to_string(&frame) {
+            ctx.text(text);
+        }
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for ChatSession {
+    fn handle(&mut self, message: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        let message = match message {
+            Ok(message) => message,
+            Err(err) => {
+                error!("Chat relay websocket error: {:?}", err);
+                ctx.stop();
+                return;
+            }
+        };
+
+        match message {
+            ws::Message::Ping(payload) => {
+                self.last_heartbeat = Instant::now();
+                ctx.pong(&payload);
+            }
+            ws::Message::Pong(_) => self.last_heartbeat = Instant::now(),
+            ws::Message::Text(text) => {
+                let frame: RelayFrame = match serde_json::from_str(&text) {
+                    Ok(frame) => frame,
+                    Err(err) => {
+                        error!("Invalid chat relay frame: {:?}", err);
+                        return;
+                    }
+                };
+
+                match &frame {
+                    RelayFrame::Register { peer_id } => {
+                        if self.relay.register(peer_id.clone(), self.session_token, ctx.address()) {
+                            self.peer_id = Some(peer_id.clone());
+                        } else {
+                            error!("Chat relay peer_id '{}' is already held by a connected session", peer_id);
+                            ctx.stop();
+                        }
+                    }
+                    RelayFrame::Message { to, .. } => {
+                        if let Some(recipient) = self.relay.recipient(to) {
+                            recipient.do_send(frame);
+                        }
+                    }
+                }
+            }
+            ws::Message::Close(reason) => {
+                ctx.close(reason);
+                ctx.stop();
+            }
+            _ => {}
+        }
+    }
+}
+
+pub async fn relay(request: HttpRequest, stream: web::Payload, chat_relay: web::Data<ChatRelay>) -> Result<HttpResponse, Error> {
+    ws::start(ChatSession::new(chat_relay.clone()), &request, stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+    use futures::stream;
+
+    use super::*;
+
+    fn websocket_handshake_request() -> HttpRequest {
+        TestRequest::get()
+            .header("upgrade", "websocket")
+            .header("connection", "upgrade")
+            .header("sec-websocket-version", "13")
+            .header("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==")
+            .to_http_request()
+    }
+
+    /// The returned response owns the session's mailbox; the session stays connected only as
+    /// long as the caller keeps it alive.
+    fn start_session(relay: &web::Data<ChatRelay>) -> (Addr<ChatSession>, HttpResponse) {
+        ws::start_with_addr(ChatSession::new(relay.clone()), &websocket_handshake_request(), stream::empty())
+            .expect("Chat session should start")
+    }
+
+    #[actix_rt::test]
+    async fn register_refuses_to_hijack_a_still_connected_peer() {
+        let relay = web::Data::new(ChatRelay::new());
+        let (first, _first_response) = start_session(&relay);
+        let (second, _second_response) = start_session(&relay);
+
+        assert!(relay.register("alice".to_string(), 1, first));
+        // A second, distinct session asserting the same peer_id is refused while the first one
+        // is still connected, instead of silently stealing its relay slot.
+        assert!(!relay.register("alice".to_string(), 2, second));
+    }
+
+    #[actix_rt::test]
+    async fn unregister_only_removes_its_own_registration() {
+        let relay = web::Data::new(ChatRelay::new());
+        let (addr, _response) = start_session(&relay);
+
+        assert!(relay.register("alice".to_string(), 1, addr));
+        // A stale session (a different token) can't evict the live registration.
+        relay.unregister("alice", 2);
+        assert!(relay.recipient("alice").is_some());
+
+        relay.unregister("alice", 1);
+        assert!(relay.recipient("alice").is_none());
+    }
+}
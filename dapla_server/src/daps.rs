@@ -4,23 +4,26 @@ use std::{
 };
 
 use actix_files::Files;
-use actix_web::web;
+use actix_web::{middleware, web};
 pub use dapla_common::dap::access::*;
 use log::error;
 use serde::{Deserialize, Serialize};
-use wasmer::{imports, Instance, Module, Store};
+use wasmer::{Instance, Module, Store};
 
 pub use self::{manager::*, service::*, settings::*};
 use crate::error::ServerResult;
 
 pub mod handler;
+mod host;
+pub mod install;
 mod manager;
 mod service;
 mod settings;
+pub mod watcher;
 
 type CommonDap = dapla_common::dap::Dap<PathBuf>;
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(transparent)]
 pub struct Dap(CommonDap);
 
@@ -74,6 +77,14 @@ impl Dap {
         self.0.settings().save(path)
     }
 
+    pub fn set_settings(&mut self, settings: DapSettings) {
+        self.0.set_settings(settings);
+    }
+
+    pub fn settings(&self) -> &DapSettings {
+        self.0.settings()
+    }
+
     pub fn enabled(&self) -> bool {
         self.0.enabled()
     }
@@ -114,30 +125,74 @@ impl Dap {
         self.root_dir().join(&format!("{}_server.wasm", self.name()))
     }
 
+    pub fn required_permissions(&self) -> impl Iterator<Item = &Permission> {
+        self.0.required_permissions()
+    }
+
+    pub fn allowed_permissions(&self) -> impl Iterator<Item = &Permission> {
+        self.0.allowed_permissions()
+    }
+
+    pub fn is_allowed_permission(&self, permission: &Permission) -> bool {
+        self.0.is_allowed_permission(permission)
+    }
+
+    /// `Content-Security-Policy` value for this dap's pages, derived from its `embedding`
+    /// settings and defaulting to denying framing entirely.
+    pub fn content_security_policy(&self) -> String {
+        let embedding = &self.settings().embedding;
+
+        let frame_ancestors = if !embedding.allow_embedding {
+            "'none'".to_string()
+        } else if embedding.allowed_frame_ancestors.is_empty() {
+            "'self'".to_string()
+        } else {
+            embedding.allowed_frame_ancestors.join(" ")
+        };
+
+        let mut csp = format!("frame-ancestors {};", frame_ancestors);
+        if let Some(extra) = &embedding.content_security_policy {
+            csp.push(' ');
+            csp.push_str(extra);
+        }
+        csp
+    }
+
+    /// `X-Frame-Options` fallback for browsers that don't honor `frame-ancestors`.
+    pub fn x_frame_options(&self) -> &'static str {
+        if self.settings().embedding.allow_embedding {
+            "SAMEORIGIN"
+        } else {
+            "DENY"
+        }
+    }
+
+    /// Registers the static routes for this dap's own client bundle. Only called for the main
+    /// (`dapla`) dap; every other dap goes through `handler::dispatch` instead.
     pub fn http_configure(&self) -> impl FnOnce(&mut web::ServiceConfig) + '_ {
         let name = self.name().to_string();
         let root_uri = self.root_uri();
         let static_uri = self.static_uri();
         let static_dir = self.static_dir();
-        let is_main_client = self.is_main();
+        let csp = self.content_security_policy();
+        let x_frame_options = self.x_frame_options();
 
         move |config| {
             config
                 .route(
                     &root_uri,
-                    web::get().to({
-                        let name = name.clone();
-                        move |daps_service, request| handler::index_file(daps_service, request, name.clone())
-                    }),
+                    web::get().to(move |daps_service, request| handler::index_file(daps_service, request, name.clone())),
                 )
-                .service(Files::new(&static_uri, static_dir).index_file(Self::index_file_name()));
-
-            if !is_main_client {
-                config.service(web::scope(&root_uri).route(
-                    "/*",
-                    web::get().to(move |daps_service, request| handler::get(daps_service, request, name.clone())),
-                ));
-            }
+                .service(
+                    web::scope(&static_uri)
+                        .wrap(
+                            middleware::DefaultHeaders::new()
+                                .header("Content-Security-Policy", csp)
+                                .header("X-Frame-Options", x_frame_options)
+                                .header("X-Content-Type-Options", "nosniff"),
+                        )
+                        .service(Files::new("", static_dir).index_file(Self::index_file_name())),
+                );
         }
     }
 
@@ -146,24 +201,27 @@ impl Dap {
 
         let store = Store::default();
         let module = Module::new(&store, &wasm)?;
-        let import_object = imports! {};
+        let import_object = host::import_object(self, &store);
         Instance::new(&module, &import_object).map_err(Into::into)
     }
 
-    pub fn update(&mut self, query: DapUpdateQuery) -> DapSettingsResult<bool> {
-        let DapUpdateQuery { enabled } = query;
-        if let Some(enabled) = enabled {
+    pub fn update(&mut self, query: DapUpdateQuery) -> DapSettingsResult<DapUpdateQuery> {
+        if let Some(enabled) = query.enabled {
             if self.enabled() != enabled {
                 self.set_enabled(enabled);
                 self.save_settings()?;
-                return Ok(true);
+                return Ok(query);
             }
         }
-        Ok(false)
+        Ok(DapUpdateQuery {
+            dap_name: query.dap_name,
+            enabled: None,
+        })
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct DapUpdateQuery {
+    pub dap_name: String,
     pub enabled: Option<bool>,
 }
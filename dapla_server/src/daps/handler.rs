@@ -0,0 +1,77 @@
+use actix_files::NamedFile;
+use actix_web::{
+    http::{HeaderName, HeaderValue},
+    web, HttpRequest, HttpResponse,
+};
+
+use super::DapsService;
+
+pub async fn index_file(daps_service: web::Data<DapsService>, _request: HttpRequest, name: String) -> HttpResponse {
+    let (index_file, csp, x_frame_options) = {
+        let daps_manager = daps_service.lock().expect("Daps manager lock should be acquired");
+        match daps_manager.dap(&name) {
+            Ok(dap) => (dap.index_file(), dap.content_security_policy(), dap.x_frame_options()),
+            Err(err) => return HttpResponse::NotFound().body(err.to_string()),
+        }
+    };
+
+    let response = match NamedFile::open(index_file) {
+        Ok(file) => file.into_response(&_request).unwrap_or_else(|_| HttpResponse::InternalServerError().finish()),
+        Err(err) => return HttpResponse::NotFound().body(err.to_string()),
+    };
+    with_embedding_headers(response, &csp, x_frame_options)
+}
+
+/// Single catch-all entry point for every non-main dap.
+pub async fn dispatch(daps_service: web::Data<DapsService>, request: HttpRequest) -> HttpResponse {
+    let dap_name = request.match_info().get("dap_name").unwrap_or_default().to_string();
+    let tail = request.match_info().get("tail").unwrap_or_default().to_string();
+
+    {
+        let daps_manager = daps_service.lock().expect("Daps manager lock should be acquired");
+        match daps_manager.dap(&dap_name) {
+            Ok(dap) if dap.enabled() => {}
+            Ok(_) => return HttpResponse::NotFound().body(format!("Dap '{}' is disabled", dap_name)),
+            Err(err) => return HttpResponse::NotFound().body(err.to_string()),
+        }
+    }
+
+    if tail.is_empty() {
+        index_file(daps_service, request, dap_name).await
+    } else {
+        get(daps_service, request, dap_name).await
+    }
+}
+
+pub async fn get(daps_service: web::Data<DapsService>, request: HttpRequest, name: String) -> HttpResponse {
+    let tail = request.match_info().get("tail").unwrap_or_default().to_string();
+    let (file_path, csp, x_frame_options) = {
+        let daps_manager = daps_service.lock().expect("Daps manager lock should be acquired");
+        match daps_manager.dap(&name) {
+            Ok(dap) => (dap.static_dir().join(tail), dap.content_security_policy(), dap.x_frame_options()),
+            Err(err) => return HttpResponse::NotFound().body(err.to_string()),
+        }
+    };
+
+    let response = match NamedFile::open(file_path) {
+        Ok(file) => file.into_response(&request).unwrap_or_else(|_| HttpResponse::InternalServerError().finish()),
+        Err(err) => return HttpResponse::NotFound().body(err.to_string()),
+    };
+    with_embedding_headers(response, &csp, x_frame_options)
+}
+
+/// A dap's own CSP failed to parse into a header value; since this is the only place non-main
+/// dap content leaves the server, fall back to denying framing entirely rather than shipping no
+/// CSP at all.
+const DENY_BY_DEFAULT_CSP: &str = "frame-ancestors 'none';";
+
+/// Applies a dap's CSP/framing policy on top of a file response, defaulting to deny-framing
+/// since this is the only place non-main dap content leaves the server.
+fn with_embedding_headers(mut response: HttpResponse, csp: &str, x_frame_options: &'static str) -> HttpResponse {
+    let headers = response.headers_mut();
+    let csp_value = HeaderValue::from_str(csp).unwrap_or_else(|_| HeaderValue::from_static(DENY_BY_DEFAULT_CSP));
+    headers.insert(HeaderName::from_static("content-security-policy"), csp_value);
+    headers.insert(HeaderName::from_static("x-frame-options"), HeaderValue::from_static(x_frame_options));
+    headers.insert(HeaderName::from_static("x-content-type-options"), HeaderValue::from_static("nosniff"));
+    response
+}
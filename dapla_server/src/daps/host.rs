@@ -0,0 +1,362 @@
+use std::path::PathBuf;
+
+use dapla_common::dap::access::Permission;
+use log::{debug, warn};
+use wasmer::{Exports, Function, ImportObject, LazyInit, Memory, Store, WasmerEnv};
+
+use super::Dap;
+
+/// Per-import environment identifying which dap a host function was installed
+/// for, so every call can re-check `is_allowed_permission` before touching
+/// anything outside the sandbox.
+#[derive(Debug, Clone, WasmerEnv)]
+pub struct DapHandle {
+    name: String,
+    root_dir: PathBuf,
+    allowed_permissions: Vec<Permission>,
+    #[wasmer(export)]
+    memory: LazyInit<Memory>,
+}
+
+impl DapHandle {
+    fn new(dap: &Dap) -> Self {
+        Self {
+            name: dap.name().to_string(),
+            root_dir: dap.root_dir().to_path_buf(),
+            allowed_permissions: dap.allowed_permissions().copied().collect(),
+            memory: LazyInit::new(),
+        }
+    }
+
+    fn is_allowed(&self, permission: Permission) -> bool {
+        self.allowed_permissions.contains(&permission)
+    }
+
+    /// Resolves a dap-relative path, rejecting anything that would escape `root_dir`. The parent
+    /// directory is canonicalized so this also works for paths that don't exist yet (e.g. a file
+    /// `host_write` is about to create); if the final component already exists, it's
+    /// canonicalized too so a symlink planted inside `root_dir` can't point back out of it.
+    fn resolve_in_sandbox(&self, relative_path: &str) -> Option<PathBuf> {
+        let path = self.root_dir.join(relative_path);
+        let file_name = path.file_name()?;
+        let parent = path.parent()?.canonicalize().ok()?;
+        let root_dir = self.root_dir.canonicalize().ok()?;
+        if !parent.starts_with(&root_dir) {
+            return None;
+        }
+
+        let resolved = parent.join(file_name);
+        match resolved.canonicalize() {
+            Ok(canonical) => canonical.starts_with(&root_dir).then(|| canonical),
+            Err(_) => Some(resolved),
+        }
+    }
+
+    fn memory(&self) -> &Memory {
+        self.memory.get_ref().expect("Memory should be initialized")
+    }
+
+    /// Reads `len` bytes starting at `ptr` out of the dap's linear memory, rejecting any range
+    /// that overflows or falls outside the currently allocated memory instead of panicking.
+    fn read_bytes(&self, ptr: u32, len: u32) -> Option<Vec<u8>> {
+        let memory = self.memory();
+        let end = (ptr as u64).checked_add(len as u64)?;
+        if end > memory.size().bytes().0 as u64 {
+            return None;
+        }
+
+        let view = memory.view::<u8>();
+        Some(view[ptr as usize..end as usize].iter().map(|cell| cell.get()).collect())
+    }
+
+    fn read_string(&self, ptr: u32, len: u32) -> Option<String> {
+        self.read_bytes(ptr, len).map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    /// Copies `data`, truncated to `out_len`, into the dap's linear memory at `out_ptr`.
+    /// Returns the number of bytes written, or `None` if `out_ptr`/`out_len` fall outside the
+    /// currently allocated memory.
+    fn write_bytes(&self, out_ptr: u32, out_len: u32, data: &[u8]) -> Option<u32> {
+        let memory = self.memory();
+        let end = (out_ptr as u64).checked_add(out_len as u64)?;
+        if end > memory.size().bytes().0 as u64 {
+            return None;
+        }
+
+        let written = data.len().min(out_len as usize);
+        let view = memory.view::<u8>();
+        for (cell, byte) in view[out_ptr as usize..out_ptr as usize + written].iter().zip(data) {
+            cell.set(*byte);
+        }
+        Some(written as u32)
+    }
+}
+
+/// Builds the import object for `dap`, installing only the host functions
+/// that correspond to permissions it was actually granted.
+pub fn import_object(dap: &Dap, store: &Store) -> ImportObject {
+    let mut import_object = ImportObject::new();
+    let mut env = Exports::new();
+
+    let handle = DapHandle::new(dap);
+    env.insert("host_log", Function::new_native_with_env(store, handle.clone(), host_log));
+
+    for permission in dap.allowed_permissions() {
+        match permission {
+            Permission::Http => {
+                env.insert(
+                    "host_http_fetch",
+                    Function::new_native_with_env(store, handle.clone(), host_http_fetch),
+                );
+            }
+            Permission::FileRead => {
+                env.insert("host_read", Function::new_native_with_env(store, handle.clone(), host_read));
+            }
+            Permission::FileWrite => {
+                env.insert("host_write", Function::new_native_with_env(store, handle.clone(), host_write));
+            }
+            Permission::Tcp | Permission::Websocket => {
+                env.insert(
+                    "host_socket_connect",
+                    Function::new_native_with_env(store, handle.clone(), host_socket_connect),
+                );
+            }
+        }
+    }
+
+    import_object.register("env", env);
+    import_object
+}
+
+fn host_log(env: &DapHandle, ptr: u32, len: u32) {
+    match env.read_string(ptr, len) {
+        Some(message) => debug!("[{}] {}", env.name, message),
+        None => warn!("Dap '{}' called host_log with an out-of-bounds buffer", env.name),
+    }
+}
+
+/// Fetches `url` and copies up to `out_len` bytes of the response body into the dap's memory at
+/// `out_ptr`. Returns the number of bytes written, or `-1` on failure (including an out-of-bounds
+/// `url_ptr`/`url_len` or `out_ptr`/`out_len`).
+fn host_http_fetch(env: &DapHandle, url_ptr: u32, url_len: u32, out_ptr: u32, out_len: u32) -> i32 {
+    if !env.is_allowed(Permission::Http) {
+        warn!("Dap '{}' called host_http_fetch without the Http permission", env.name);
+        return -1;
+    }
+
+    let url = match env.read_string(url_ptr, url_len) {
+        Some(url) => url,
+        None => {
+            warn!("Dap '{}' called host_http_fetch with an out-of-bounds url buffer", env.name);
+            return -1;
+        }
+    };
+
+    let body = match reqwest::blocking::get(&url).and_then(|response| response.bytes()) {
+        Ok(body) => body,
+        Err(err) => {
+            warn!("Dap '{}' host_http_fetch error for '{}': {:?}", env.name, url, err);
+            return -1;
+        }
+    };
+
+    match env.write_bytes(out_ptr, out_len, &body) {
+        Some(written) => written as i32,
+        None => {
+            warn!("Dap '{}' host_http_fetch out buffer is out-of-bounds", env.name);
+            -1
+        }
+    }
+}
+
+/// Reads `path` and copies up to `out_len` bytes of its contents into the dap's memory at
+/// `out_ptr`. Returns the number of bytes written, or `-1` on failure.
+fn host_read(env: &DapHandle, path_ptr: u32, path_len: u32, out_ptr: u32, out_len: u32) -> i32 {
+    if !env.is_allowed(Permission::FileRead) {
+        warn!("Dap '{}' called host_read without the FileRead permission", env.name);
+        return -1;
+    }
+
+    let path = match env.read_string(path_ptr, path_len) {
+        Some(path) => path,
+        None => {
+            warn!("Dap '{}' called host_read with an out-of-bounds path buffer", env.name);
+            return -1;
+        }
+    };
+
+    let contents = match env.resolve_in_sandbox(&path) {
+        Some(path) => match std::fs::read(path) {
+            Ok(contents) => contents,
+            Err(err) => {
+                warn!("Dap '{}' host_read error: {:?}", env.name, err);
+                return -1;
+            }
+        },
+        None => {
+            warn!("Dap '{}' host_read path '{}' escapes its root_dir", env.name, path);
+            return -1;
+        }
+    };
+
+    match env.write_bytes(out_ptr, out_len, &contents) {
+        Some(written) => written as i32,
+        None => {
+            warn!("Dap '{}' host_read out buffer is out-of-bounds", env.name);
+            -1
+        }
+    }
+}
+
+fn host_write(env: &DapHandle, path_ptr: u32, path_len: u32, data_ptr: u32, data_len: u32) -> i32 {
+    if !env.is_allowed(Permission::FileWrite) {
+        warn!("Dap '{}' called host_write without the FileWrite permission", env.name);
+        return -1;
+    }
+
+    let path = match env.read_string(path_ptr, path_len) {
+        Some(path) => path,
+        None => {
+            warn!("Dap '{}' called host_write with an out-of-bounds path buffer", env.name);
+            return -1;
+        }
+    };
+    let data = match env.read_bytes(data_ptr, data_len) {
+        Some(data) => data,
+        None => {
+            warn!("Dap '{}' called host_write with an out-of-bounds data buffer", env.name);
+            return -1;
+        }
+    };
+
+    match env.resolve_in_sandbox(&path) {
+        Some(path) => match std::fs::write(path, data) {
+            Ok(()) => 0,
+            Err(err) => {
+                warn!("Dap '{}' host_write error: {:?}", env.name, err);
+                -1
+            }
+        },
+        None => {
+            warn!("Dap '{}' host_write path '{}' escapes its root_dir", env.name, path);
+            -1
+        }
+    }
+}
+
+fn host_socket_connect(env: &DapHandle, addr_ptr: u32, addr_len: u32) -> i32 {
+    let allowed = env.is_allowed(Permission::Tcp) || env.is_allowed(Permission::Websocket);
+    if !allowed {
+        warn!("Dap '{}' called host_socket_connect without Tcp/Websocket permission", env.name);
+        return -1;
+    }
+
+    let addr = match env.read_string(addr_ptr, addr_len) {
+        Some(addr) => addr,
+        None => {
+            warn!("Dap '{}' called host_socket_connect with an out-of-bounds addr buffer", env.name);
+            return -1;
+        }
+    };
+
+    match std::net::TcpStream::connect(&addr) {
+        Ok(_stream) => 0,
+        Err(err) => {
+            warn!("Dap '{}' host_socket_connect error for '{}': {:?}", env.name, addr, err);
+            -1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasmer::{MemoryType, Pages};
+
+    use super::*;
+
+    fn handle_for(root_dir: &std::path::Path) -> DapHandle {
+        DapHandle {
+            name: "test-dap".to_string(),
+            root_dir: root_dir.to_path_buf(),
+            allowed_permissions: Vec::new(),
+            memory: LazyInit::new(),
+        }
+    }
+
+    fn handle_with_memory(root_dir: &std::path::Path) -> DapHandle {
+        let handle = handle_for(root_dir);
+        let memory = Memory::new(&Store::default(), MemoryType::new(Pages(1), None, false)).expect("Memory should be created");
+        handle.memory.initialize(memory);
+        handle
+    }
+
+    #[test]
+    fn resolve_in_sandbox_rejects_parent_traversal() {
+        let root = tempfile::tempdir().expect("Tempdir should be created");
+        let handle = handle_for(root.path());
+        assert!(handle.resolve_in_sandbox("../escape.txt").is_none());
+    }
+
+    #[test]
+    fn resolve_in_sandbox_rejects_an_absolute_path_outside_root() {
+        let root = tempfile::tempdir().expect("Tempdir should be created");
+        let outside = tempfile::tempdir().expect("Tempdir should be created");
+        let handle = handle_for(root.path());
+        let absolute = outside.path().join("secret.txt");
+        assert!(handle.resolve_in_sandbox(absolute.to_str().expect("Path should be UTF-8")).is_none());
+    }
+
+    #[test]
+    fn resolve_in_sandbox_allows_a_new_file_inside_root() {
+        let root = tempfile::tempdir().expect("Tempdir should be created");
+        let handle = handle_for(root.path());
+        let resolved = handle.resolve_in_sandbox("new-file.txt").expect("Path should resolve inside root");
+        assert_eq!(resolved, root.path().canonicalize().expect("Root should canonicalize").join("new-file.txt"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn resolve_in_sandbox_rejects_a_symlink_escaping_root() {
+        use std::{fs, os::unix::fs::symlink};
+
+        let root = tempfile::tempdir().expect("Tempdir should be created");
+        let outside = tempfile::tempdir().expect("Tempdir should be created");
+        let target = outside.path().join("secret.txt");
+        fs::write(&target, b"secret").expect("File should be written");
+
+        let link = root.path().join("link.txt");
+        symlink(&target, &link).expect("Symlink should be created");
+
+        let handle = handle_for(root.path());
+        assert!(handle.resolve_in_sandbox("link.txt").is_none());
+    }
+
+    #[test]
+    fn read_bytes_rejects_an_overflowing_range() {
+        let root = tempfile::tempdir().expect("Tempdir should be created");
+        let handle = handle_with_memory(root.path());
+
+        // `ptr + len` exceeds `u32::MAX` (it would wrap in 32-bit arithmetic).
+        assert!(handle.read_bytes(u32::MAX, u32::MAX).is_none());
+        // Doesn't overflow, but is far larger than the single page of memory allocated above.
+        assert!(handle.read_bytes(0, u32::MAX).is_none());
+    }
+
+    #[test]
+    fn write_bytes_rejects_an_overflowing_range() {
+        let root = tempfile::tempdir().expect("Tempdir should be created");
+        let handle = handle_with_memory(root.path());
+
+        assert!(handle.write_bytes(u32::MAX, u32::MAX, b"data").is_none());
+        assert!(handle.write_bytes(0, u32::MAX, b"data").is_none());
+    }
+
+    #[test]
+    fn read_bytes_and_write_bytes_round_trip_within_bounds() {
+        let root = tempfile::tempdir().expect("Tempdir should be created");
+        let handle = handle_with_memory(root.path());
+
+        assert_eq!(handle.write_bytes(0, 5, b"hello"), Some(5));
+        assert_eq!(handle.read_bytes(0, 5).expect("Read should be in bounds"), b"hello");
+    }
+}
@@ -0,0 +1,186 @@
+use std::{fs, io::Cursor, path::Path};
+
+use dapla_common::dap::access::Permission;
+use ed25519_dalek::{PublicKey, Signature, Verifier};
+use flate2::read::GzDecoder;
+use log::info;
+use serde::Deserialize;
+use tar::Archive;
+use thiserror::Error;
+
+use super::{settings::DapSettingsStorage, Dap, DapSettings};
+
+const MANIFEST_FILE_NAME: &str = "manifest.toml";
+
+#[derive(Debug, Deserialize)]
+pub struct InstallRequest {
+    /// Where to download the signed dap package (a `.tar.gz` archive) from.
+    pub package_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DapManifest {
+    name: String,
+    #[allow(dead_code)]
+    version: String,
+    title: String,
+    entry: String,
+    #[serde(default)]
+    required_permissions: Vec<Permission>,
+    #[serde(default)]
+    allowed_permissions: Vec<Permission>,
+}
+
+/// Downloads, verifies and unpacks a dap package, returning it ready to be registered.
+///
+/// The package must contain a `manifest.toml`. Its detached ed25519 signature over the raw
+/// archive bytes is downloaded from `<package_url>.sig` and must verify against one of
+/// `trusted_publisher_keys`.
+///
+/// This only touches `daps_path` on disk and never locks `DapsManager`, since the network
+/// download dominates its running time; callers should hold the manager lock only for the final
+/// `register()` call, not for the whole install.
+pub async fn install(daps_path: &Path, request: InstallRequest, trusted_publisher_keys: &[String]) -> InstallResult<Dap> {
+    let archive_bytes = reqwest::get(&request.package_url).await?.bytes().await?;
+    let signature_bytes = reqwest::get(&format!("{}.sig", request.package_url)).await?.bytes().await?;
+
+    verify_signature(&archive_bytes, &signature_bytes, trusted_publisher_keys)?;
+
+    let manifest = read_manifest(&archive_bytes)?;
+    validate_dap_name(&manifest.name)?;
+    let dap_dir = daps_path.join(&manifest.name);
+    fs::create_dir_all(&dap_dir)?;
+
+    let decoder = GzDecoder::new(Cursor::new(archive_bytes.as_ref()));
+    Archive::new(decoder).unpack(&dap_dir)?;
+
+    let mut dap = Dap::new(manifest.name.clone(), dap_dir.clone());
+    let entry_wasm = dap_dir.join(&manifest.entry);
+    if entry_wasm != dap.server_module_file() && entry_wasm.exists() {
+        fs::rename(entry_wasm, dap.server_module_file())?;
+    }
+    let mut settings = DapSettings::default();
+    settings.application.enabled = false;
+    settings.application.title = manifest.title.clone();
+    settings.permissions.required = manifest.required_permissions.clone();
+    settings.permissions.allowed = manifest.allowed_permissions.clone();
+    dap.set_settings(settings);
+    dap.save_settings()?;
+
+    info!("Installed dap '{}' ({}) from {}", dap.name(), manifest.title, request.package_url);
+    Ok(dap)
+}
+
+/// Rejects anything but a plain directory name, so a manifest can't escape `daps_path` via `/`,
+/// `..`, or other path separators baked into `name`.
+fn validate_dap_name(name: &str) -> InstallResult<()> {
+    let is_plain_identifier = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if is_plain_identifier && name != "." && name != ".." {
+        Ok(())
+    } else {
+        Err(InstallError::InvalidDapName(name.to_string()))
+    }
+}
+
+fn verify_signature(archive_bytes: &[u8], signature_bytes: &[u8], trusted_publisher_keys: &[String]) -> InstallResult<()> {
+    let signature = Signature::from_bytes(signature_bytes)?;
+
+    let is_trusted = trusted_publisher_keys.iter().any(|encoded_key| {
+        bs58::decode(encoded_key)
+            .into_vec()
+            .ok()
+            .and_then(|bytes| PublicKey::from_bytes(&bytes).ok())
+            .map(|public_key| public_key.verify(archive_bytes, &signature).is_ok())
+            .unwrap_or(false)
+    });
+
+    if is_trusted {
+        Ok(())
+    } else {
+        Err(InstallError::UntrustedPublisher)
+    }
+}
+
+fn read_manifest(archive_bytes: &bytes::Bytes) -> InstallResult<DapManifest> {
+    let decoder = GzDecoder::new(Cursor::new(archive_bytes.as_ref()));
+    let mut archive = Archive::new(decoder);
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if entry.path()?.ends_with(MANIFEST_FILE_NAME) {
+            let mut content = String::new();
+            std::io::Read::read_to_string(&mut entry, &mut content)?;
+            return toml::from_str(&content).map_err(Into::into);
+        }
+    }
+
+    Err(InstallError::MissingManifest)
+}
+
+pub type InstallResult<T> = Result<T, InstallError>;
+
+#[derive(Debug, Error)]
+pub enum InstallError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Download error: {0}")]
+    Download(#[from] reqwest::Error),
+
+    #[error("Package is missing a {}", MANIFEST_FILE_NAME)]
+    MissingManifest,
+
+    #[error("Manifest name '{0}' is not a plain identifier")]
+    InvalidDapName(String),
+
+    #[error("Invalid manifest: {0}")]
+    InvalidManifest(#[from] toml::de::Error),
+
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(#[from] ed25519_dalek::SignatureError),
+
+    #[error("Package signature is not signed by a trusted publisher")]
+    UntrustedPublisher,
+
+    #[error("Dap settings error: {0}")]
+    DapSettings(#[from] crate::daps::DapSettingsError),
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::{Keypair, Signer};
+    use rand::rngs::OsRng;
+
+    use super::*;
+
+    #[test]
+    fn verify_signature_rejects_a_signature_from_an_untrusted_key() {
+        let trusted = Keypair::generate(&mut OsRng);
+        let untrusted = Keypair::generate(&mut OsRng);
+        let archive_bytes = b"fake archive contents";
+        let signature = untrusted.sign(archive_bytes);
+
+        let trusted_publisher_keys = vec![bs58::encode(trusted.public.as_bytes()).into_string()];
+        let result = verify_signature(archive_bytes, &signature.to_bytes(), &trusted_publisher_keys);
+        assert!(matches!(result, Err(InstallError::UntrustedPublisher)));
+    }
+
+    #[test]
+    fn verify_signature_accepts_a_signature_from_a_trusted_key() {
+        let trusted = Keypair::generate(&mut OsRng);
+        let archive_bytes = b"fake archive contents";
+        let signature = trusted.sign(archive_bytes);
+
+        let trusted_publisher_keys = vec![bs58::encode(trusted.public.as_bytes()).into_string()];
+        let result = verify_signature(archive_bytes, &signature.to_bytes(), &trusted_publisher_keys);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn validate_dap_name_rejects_path_traversal() {
+        assert!(validate_dap_name("../../etc").is_err());
+        assert!(validate_dap_name("a/b").is_err());
+        assert!(validate_dap_name("..").is_err());
+        assert!(validate_dap_name("my-dap_1").is_ok());
+    }
+}
@@ -0,0 +1,116 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use log::error;
+use thiserror::Error;
+
+use super::Dap;
+use crate::error::ServerResult;
+
+pub struct DapsManager {
+    daps_path: PathBuf,
+    daps: HashMap<String, Dap>,
+}
+
+impl DapsManager {
+    pub fn new(daps_path: impl Into<PathBuf>) -> io::Result<Self> {
+        let daps_path = daps_path.into();
+        fs::create_dir_all(&daps_path)?;
+        Ok(Self {
+            daps_path,
+            daps: HashMap::new(),
+        })
+    }
+
+    pub fn daps_path(&self) -> &Path {
+        &self.daps_path
+    }
+
+    /// Rescans `daps_path`: registers newly appeared dap directories, drops ones that were
+    /// removed, and reloads `settings.toml` for directories that were already known.
+    pub fn load_daps(&mut self) {
+        let entries = match fs::read_dir(&self.daps_path) {
+            Ok(entries) => entries,
+            Err(err) => {
+                error!("Error when reading daps directory '{:?}': {:?}", self.daps_path, err);
+                return;
+            }
+        };
+
+        let mut seen = HashSet::new();
+        let mut newly_registered = Vec::new();
+
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            let name = match path.file_name().and_then(|name| name.to_str()) {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+
+            seen.insert(name.clone());
+            match self.daps.get_mut(&name) {
+                // The main dap has no `settings.toml` (`Dap::new` skips loading settings for it
+                // too), so reloading it here would just log a spurious error on every scan.
+                Some(dap) if dap.is_main() => {}
+                Some(dap) => {
+                    if let Err(err) = dap.reload_settings() {
+                        error!("Error when reloading settings for dap '{}': {:?}", name, err);
+                    }
+                }
+                None => {
+                    self.register(Dap::new(name.clone(), path));
+                    newly_registered.push(name);
+                }
+            }
+        }
+
+        self.daps.retain(|name, dap| dap.is_main() || seen.contains(name));
+
+        for name in newly_registered {
+            if self.daps.get(&name).map(Dap::enabled).unwrap_or(false) {
+                if let Err(err) = self.load(&name) {
+                    error!("Error when loading newly discovered dap '{}': {:?}", name, err);
+                }
+            }
+        }
+    }
+
+    pub fn register(&mut self, dap: Dap) {
+        self.daps.insert(dap.name().to_string(), dap);
+    }
+
+    pub fn daps_iter(&self) -> impl Iterator<Item = &Dap> {
+        self.daps.values()
+    }
+
+    pub fn dap(&self, name: &str) -> ServerResult<&Dap> {
+        self.daps.get(name).ok_or_else(|| ManagerError::DapNotFound(name.to_string()).into())
+    }
+
+    pub fn dap_mut(&mut self, name: &str) -> ServerResult<&mut Dap> {
+        self.daps
+            .get_mut(name)
+            .ok_or_else(|| ManagerError::DapNotFound(name.to_string()).into())
+    }
+
+    pub fn load(&mut self, name: impl AsRef<str>) -> ServerResult<()> {
+        self.dap(name.as_ref())?.instantiate()?;
+        Ok(())
+    }
+
+    pub fn unload(&mut self, name: impl AsRef<str>) {
+        let _ = name.as_ref();
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ManagerError {
+    #[error("Dap '{0}' is not found")]
+    DapNotFound(String),
+}
@@ -0,0 +1,37 @@
+use std::{
+    io,
+    ops::Deref,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use actix_web::{HttpResponse, ResponseError};
+use log::error;
+
+use super::DapsManager;
+use crate::error::ServerResult;
+
+#[derive(Clone)]
+pub struct DapsService(Arc<Mutex<DapsManager>>);
+
+impl DapsService {
+    pub fn new(daps_path: impl Into<PathBuf>) -> io::Result<Self> {
+        Ok(Self(Arc::new(Mutex::new(DapsManager::new(daps_path)?))))
+    }
+
+    pub async fn handle_http(&self, handle: impl FnOnce(&mut DapsManager) -> ServerResult<HttpResponse>) -> HttpResponse {
+        let mut daps_manager = self.lock().expect("Daps manager lock should be acquired");
+        handle(&mut daps_manager).unwrap_or_else(|err| {
+            error!("{:?}", err);
+            err.error_response()
+        })
+    }
+}
+
+impl Deref for DapsService {
+    type Target = Mutex<DapsManager>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
@@ -0,0 +1,63 @@
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+pub use dapla_common::dap::DapSettings;
+
+use super::DapUpdateQuery;
+
+pub type DapSettingsResult<T> = Result<T, DapSettingsError>;
+
+#[derive(Debug, Error)]
+pub enum DapSettingsError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Toml deserialization error: {0}")]
+    TomlDe(#[from] toml::de::Error),
+
+    #[error("Toml serialization error: {0}")]
+    TomlSer(#[from] toml::ser::Error),
+}
+
+/// Load/save for `DapSettings` lives here, separate from `dapla_common`,
+/// because only the server ever persists settings to disk.
+pub trait DapSettingsStorage: Sized {
+    fn load(path: impl AsRef<Path>) -> DapSettingsResult<Self>;
+    fn save(&self, path: impl AsRef<Path>) -> DapSettingsResult<()>;
+}
+
+impl DapSettingsStorage for DapSettings {
+    fn load(path: impl AsRef<Path>) -> DapSettingsResult<Self> {
+        let content = fs::read_to_string(path)?;
+        toml::from_str(&content).map_err(Into::into)
+    }
+
+    fn save(&self, path: impl AsRef<Path>) -> DapSettingsResult<()> {
+        let content = toml::to_string_pretty(self)?;
+        fs::write(path, content).map_err(Into::into)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DapUpdateRequest {
+    pub dap_name: String,
+    pub enabled: Option<bool>,
+}
+
+impl DapUpdateRequest {
+    pub fn into_query(self) -> DapUpdateQuery {
+        DapUpdateQuery {
+            dap_name: self.dap_name,
+            enabled: self.enabled,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", content = "data")]
+pub enum DapResponse<'a> {
+    Daps(Vec<std::borrow::Cow<'a, super::Dap>>),
+    Updated(DapUpdateQuery),
+}
@@ -0,0 +1,44 @@
+use std::{path::Path, sync::mpsc::channel, thread, time::Duration};
+
+use log::{error, info};
+use notify::{DebouncedEvent, RecursiveMode, Watcher};
+
+use super::DapsService;
+
+/// Watches `daps_path` for added/removed dap directories and keeps `daps_service` in sync with
+/// the filesystem, so dropping a dap directory in (or deleting one) takes effect immediately
+/// instead of requiring a server restart.
+pub fn watch(daps_service: DapsService, daps_path: impl AsRef<Path>) {
+    let daps_path = daps_path.as_ref().to_path_buf();
+
+    thread::spawn(move || {
+        let (sender, receiver) = channel();
+        let mut watcher = match notify::watcher(sender, Duration::from_secs(1)) {
+            Ok(watcher) => watcher,
+            Err(err) => {
+                error!("Error when creating daps directory watcher: {:?}", err);
+                return;
+            }
+        };
+
+        if let Err(err) = watcher.watch(&daps_path, RecursiveMode::NonRecursive) {
+            error!("Error when watching daps directory '{:?}': {:?}", daps_path, err);
+            return;
+        }
+
+        loop {
+            match receiver.recv() {
+                Ok(DebouncedEvent::Create(_)) | Ok(DebouncedEvent::Remove(_)) | Ok(DebouncedEvent::Rename(_, _)) => {
+                    info!("Daps directory '{:?}' changed, reloading daps", daps_path);
+                    let mut daps_manager = daps_service.lock().expect("Daps manager lock should be acquired");
+                    daps_manager.load_daps();
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    error!("Daps directory watcher error: {:?}", err);
+                    break;
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,43 @@
+use std::io;
+
+use actix_web::{HttpResponse, ResponseError};
+use thiserror::Error;
+
+use crate::daps::{install::InstallError, DapSettingsError, ManagerError};
+
+pub type ServerResult<T> = Result<T, ServerError>;
+
+#[derive(Debug, Error)]
+pub enum ServerError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Wasm compile error: {0}")]
+    WasmCompile(#[from] wasmer::CompileError),
+
+    #[error("Wasm instantiation error: {0}")]
+    WasmInstantiation(#[from] wasmer::InstantiationError),
+
+    #[error("Dap settings error: {0}")]
+    DapSettings(#[from] DapSettingsError),
+
+    #[error("Daps manager error: {0}")]
+    Manager(#[from] ManagerError),
+
+    #[error("Dap install error: {0}")]
+    Install(#[from] InstallError),
+
+    #[error("Json error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+impl ResponseError for ServerError {
+    fn error_response(&self) -> HttpResponse {
+        match self {
+            Self::Manager(ManagerError::DapNotFound(_)) => HttpResponse::NotFound().body(self.to_string()),
+            Self::Install(_) => HttpResponse::BadRequest().body(self.to_string()),
+            Self::Json(_) => HttpResponse::BadRequest().body(self.to_string()),
+            _ => HttpResponse::InternalServerError().body(self.to_string()),
+        }
+    }
+}
@@ -1,13 +1,17 @@
 use std::{borrow::Cow, io, ops::Deref, path::PathBuf};
 
-use actix_files::{Files, NamedFile};
+use actix_files::NamedFile;
 use actix_web::{middleware, web, App, HttpResponse, HttpServer};
 
 use self::{
-    daps::{Dap, DapResponse, DapUpdateRequest, DapsService},
+    auth::{AdminPublicKeys, AdminRights, AuthService},
+    chat::ChatRelay,
+    daps::{install, Dap, DapResponse, DapUpdateRequest, DapsService},
     settings::Settings,
 };
 
+mod auth;
+mod chat;
 mod daps;
 mod error;
 mod settings;
@@ -28,7 +32,7 @@ async fn get_daps(daps_service: web::Data<DapsService>) -> HttpResponse {
         .await
 }
 
-async fn update_dap(daps_service: web::Data<DapsService>, body: String) -> HttpResponse {
+async fn update_dap(daps_service: web::Data<DapsService>, _admin: AdminRights, body: String) -> HttpResponse {
     daps_service
         .into_inner()
         .handle_http(|daps_manager| {
@@ -50,23 +54,60 @@ async fn update_dap(daps_service: web::Data<DapsService>, body: String) -> HttpR
         .await
 }
 
+async fn install_dap(
+    daps_service: web::Data<DapsService>,
+    trusted_publisher_keys: web::Data<Vec<String>>,
+    _admin: AdminRights,
+    body: String,
+) -> HttpResponse {
+    let request = match serde_json::from_str(&body) {
+        Ok(request) => request,
+        Err(err) => return HttpResponse::BadRequest().body(err.to_string()),
+    };
+
+    // The daps directory path is read without holding the lock across the `.await` below: the
+    // download dominates `install`'s running time, and every other dap-serving handler also
+    // needs this same lock, so it's only taken again afterwards for the quick `register()`.
+    let daps_path = daps_service.lock().expect("Daps manager lock should be acquired").daps_path().to_path_buf();
+
+    match install::install(&daps_path, request, &trusted_publisher_keys).await {
+        Ok(dap) => {
+            let dap_name = dap.name().to_string();
+            daps_service.lock().expect("Daps manager lock should be acquired").register(dap);
+            HttpResponse::Ok().json(dap_name)
+        }
+        Err(err) => HttpResponse::BadRequest().body(err.to_string()),
+    }
+}
+
 #[actix_web::main]
 async fn main() -> io::Result<()> {
     let settings = Settings::new().expect("Settings should be configured");
     env_logger::init_from_env(env_logger::Env::new().default_filter_or(settings.log.level.to_string()));
 
     let daps_service = DapsService::new(&settings.daps.path)?;
+    let trusted_publisher_keys = settings.installation.trusted_publisher_keys.clone();
+    let auth_service = web::Data::new(AuthService::new());
+    let server_secret = settings.auth.server_secret.clone();
+    let admin_public_keys = settings.auth.admin_public_keys.clone();
+    let chat_relay = web::Data::new(ChatRelay::new());
+
+    daps::watcher::watch(daps_service.clone(), &settings.daps.path);
 
     HttpServer::new(move || {
         let static_dir = PathBuf::new().join(Dap::static_dir_name());
 
         let mut app = App::new()
             .data(daps_service.clone())
+            .data(trusted_publisher_keys.clone())
+            .app_data(auth_service.clone())
+            .app_data(chat_relay.clone())
+            .data(server_secret.clone())
+            .data(AdminPublicKeys(admin_public_keys.clone()))
             .wrap(middleware::DefaultHeaders::new().header("X-Version", "0.2"))
             .wrap(middleware::NormalizePath::default())
             .wrap(middleware::Compress::default())
             .wrap(middleware::Logger::default())
-            .service(Files::new(&Dap::main_static_uri(), &static_dir).index_file(Dap::index_file_name()))
             .route(
                 "/",
                 web::get().to(move || {
@@ -75,15 +116,23 @@ async fn main() -> io::Result<()> {
                 }),
             )
             .route(&Dap::main_uri("daps"), web::get().to(get_daps))
-            .route(&Dap::main_uri("dap"), web::post().to(update_dap));
+            .route(&Dap::main_uri("dap"), web::post().to(update_dap))
+            .route(&Dap::main_uri("install"), web::post().to(install_dap))
+            .route(&Dap::main_uri2("auth", "challenge"), web::get().to(auth::challenge))
+            .route(&Dap::main_uri2("auth", "login"), web::post().to(auth::login))
+            .route(&Dap::main_uri2("chat", "relay"), web::get().to(chat::relay));
 
         let mut daps_manager = daps_service.lock().expect("Daps manager lock should be acquired");
         daps_manager.load_daps();
 
-        for dap in daps_manager.daps_iter() {
+        for dap in daps_manager.daps_iter().filter(|dap| dap.is_main()) {
             app = app.configure(dap.http_configure());
         }
-        app
+        drop(daps_manager);
+
+        // Every other dap is dispatched to at request time instead of getting its own routes,
+        // so newly installed or re-enabled daps are reachable without a restart.
+        app.route("/{dap_name}/{tail:.*}", web::get().to(daps::handler::dispatch))
     })
     .bind((settings.http.host.as_str(), settings.http.port))?
     .run()
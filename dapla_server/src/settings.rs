@@ -0,0 +1,55 @@
+use config::{Config, ConfigError, Environment, File};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct Settings {
+    pub http: HttpSettings,
+    pub daps: DapsSettings,
+    pub log: LogSettings,
+    #[serde(default)]
+    pub installation: InstallationSettings,
+    pub auth: AuthSettings,
+}
+
+impl Settings {
+    pub fn new() -> Result<Self, ConfigError> {
+        let mut config = Config::new();
+        config.merge(File::with_name("settings").required(false))?;
+        config.merge(Environment::with_prefix("dapla").separator("_"))?;
+        config.try_into()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HttpSettings {
+    pub host: String,
+    pub port: u16,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DapsSettings {
+    pub path: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LogSettings {
+    pub level: String,
+}
+
+/// Publisher keys trusted to sign installable dap packages.
+#[derive(Debug, Default, Deserialize)]
+pub struct InstallationSettings {
+    /// Base58-encoded ed25519 public keys allowed to sign `manifest.toml` packages.
+    #[serde(default)]
+    pub trusted_publisher_keys: Vec<String>,
+}
+
+/// Admin session signing configuration.
+#[derive(Debug, Deserialize)]
+pub struct AuthSettings {
+    /// Secret used to HMAC-sign admin session cookies; must stay on the server.
+    pub server_secret: String,
+    /// Base58-encoded ed25519 public keys allowed to log in as admin.
+    #[serde(default)]
+    pub admin_public_keys: Vec<String>,
+}
@@ -0,0 +1,112 @@
+use aes_gcm::{
+    aead::{Aead, NewAead},
+    Aes256Gcm, Key, Nonce,
+};
+use anyhow::{anyhow, Context, Error};
+use curve25519_dalek::{edwards::CompressedEdwardsY, montgomery::MontgomeryPoint, scalar::Scalar};
+use hkdf::Hkdf;
+use rand::RngCore;
+use sha2::{Digest, Sha256, Sha512};
+
+const HKDF_INFO: &[u8] = b"dapla-chat-e2e";
+const NONCE_LEN: usize = 12;
+
+/// Clamps the SHA-512 hash of an ed25519 seed into the scalar used for X25519 Diffie-Hellman,
+/// so the same keypair the Chat dap already manages can be reused for encryption.
+fn secret_to_x25519_scalar(ed25519_secret_key: &[u8]) -> Scalar {
+    let hash = Sha512::digest(&ed25519_secret_key[..32]);
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(&hash[..32]);
+    bytes[0] &= 248;
+    bytes[31] &= 127;
+    bytes[31] |= 64;
+    Scalar::from_bits(bytes)
+}
+
+/// Maps an ed25519 public key (an Edwards point) to its X25519 (Montgomery) form.
+fn public_to_x25519_point(ed25519_public_key: &[u8]) -> Result<MontgomeryPoint, Error> {
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(ed25519_public_key);
+    CompressedEdwardsY(bytes)
+        .decompress()
+        .map(|point| point.to_montgomery())
+        .ok_or_else(|| anyhow!("Public key is not a valid ed25519 point"))
+}
+
+/// Derives the 32-byte AES-256-GCM key shared with `peer_public_key`, by running the X25519
+/// Diffie-Hellman shared secret through HKDF-SHA256.
+pub fn derive_shared_key(ed25519_secret_key: &[u8], peer_ed25519_public_key: &[u8]) -> Result<[u8; 32], Error> {
+    let scalar = secret_to_x25519_scalar(ed25519_secret_key);
+    let peer_point = public_to_x25519_point(peer_ed25519_public_key)?;
+    let shared_point = scalar * peer_point;
+
+    let hkdf = Hkdf::<Sha256>::new(None, shared_point.as_bytes());
+    let mut key = [0u8; 32];
+    hkdf.expand(HKDF_INFO, &mut key).map_err(|_| anyhow!("HKDF output length is invalid"))?;
+    Ok(key)
+}
+
+/// Encrypts `plaintext` with a fresh random nonce, returning `base64(nonce || ciphertext)`.
+pub fn encrypt(key: &[u8; 32], plaintext: &str) -> Result<String, Error> {
+    let cipher = Aes256Gcm::new(Key::from_slice(key));
+
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce_bytes);
+
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce_bytes), plaintext.as_bytes())
+        .map_err(|_| anyhow!("Encryption error"))?;
+
+    let mut payload = nonce_bytes.to_vec();
+    payload.extend_from_slice(&ciphertext);
+    Ok(base64::encode(payload))
+}
+
+/// Decrypts a `base64(nonce || ciphertext)` payload, rejecting it on GCM tag failure.
+pub fn decrypt(key: &[u8; 32], payload: &str) -> Result<String, Error> {
+    let payload = base64::decode(payload).context("Payload is not valid base64")?;
+    if payload.len() < NONCE_LEN {
+        return Err(anyhow!("Payload is too short to contain a nonce"));
+    }
+
+    let (nonce_bytes, ciphertext) = payload.split_at(NONCE_LEN);
+    let cipher = Aes256Gcm::new(Key::from_slice(key));
+
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce_bytes), ciphertext)
+        .map_err(|_| anyhow!("Message authentication failed"))?;
+    String::from_utf8(plaintext).context("Decrypted message is not valid UTF-8")
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::Keypair;
+    use rand::rngs::OsRng;
+
+    use super::*;
+
+    #[test]
+    fn derive_shared_key_is_symmetric() {
+        let alice = Keypair::generate(&mut OsRng);
+        let bob = Keypair::generate(&mut OsRng);
+
+        let alice_key = derive_shared_key(alice.secret.as_bytes(), bob.public.as_bytes()).unwrap();
+        let bob_key = derive_shared_key(bob.secret.as_bytes(), alice.public.as_bytes()).unwrap();
+        assert_eq!(alice_key, bob_key);
+    }
+
+    #[test]
+    fn encrypt_decrypt_round_trip() {
+        let key = [7u8; 32];
+        let payload = encrypt(&key, "hello, dapla").unwrap();
+        assert_eq!(decrypt(&key, &payload).unwrap(), "hello, dapla");
+    }
+
+    #[test]
+    fn decrypt_rejects_tampered_payload() {
+        let key = [7u8; 32];
+        let mut payload = base64::decode(encrypt(&key, "hello, dapla").unwrap()).unwrap();
+        *payload.last_mut().unwrap() ^= 0xff;
+        assert!(decrypt(&key, &base64::encode(payload)).is_err());
+    }
+}
@@ -3,21 +3,46 @@
 use anyhow::{anyhow, Context, Error};
 use dapla_yew::{JsonFetcher, MsgError, RawHtml};
 use libp2p_core::{identity::ed25519::Keypair, PeerId, PublicKey};
+use serde::{Deserialize, Serialize};
 use web_sys::HtmlElement;
 use yew::{
-    html, initialize, run_loop, services::console::ConsoleService, App, Component, ComponentLink, Html, InputData,
+    html, initialize, run_loop,
+    services::{
+        console::ConsoleService,
+        websocket::{WebSocketService, WebSocketStatus, WebSocketTask},
+    },
+    App, Component, ComponentLink, Html, InputData,
 };
 use yew_mdc_widgets::{auto_init, utils::dom, Button, List, ListItem, MdcWidget, TextField, TopAppBar};
 
+mod crypto;
+
+const RELAY_URI: &str = "/dapla/chat/relay";
+
 struct Keys {
     keypair: Keypair,
     public_key: String,
     secret_key: String,
 }
 
+struct ChatMessage {
+    outgoing: bool,
+    text: String,
+}
+
+struct Contact {
+    peer_id: PeerId,
+    public_key: String,
+    shared_key: [u8; 32],
+    messages: Vec<ChatMessage>,
+}
+
 struct ChatState {
     keys: Keys,
     peer_id: PeerId,
+    ws_task: Option<WebSocketTask>,
+    contacts: Vec<Contact>,
+    selected_contact: Option<usize>,
 }
 
 enum State {
@@ -30,8 +55,20 @@ struct Root {
     state: State,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum RelayFrame {
+    Register { peer_id: String },
+    Message { to: String, from: String, ciphertext: String },
+}
+
 enum Msg {
     SignIn,
+    WsStatus(WebSocketStatus),
+    WsReceived(Result<String, Error>),
+    AddContact,
+    SelectContact(usize),
+    SendMessage,
     Error(Error),
 }
 
@@ -72,6 +109,11 @@ impl Component for Root {
                 .msg_error_map(&self.link)
                 {
                     let peer_id = PeerId::from(PublicKey::Ed25519(keypair.public()));
+
+                    let callback = self.link.callback(Msg::WsReceived);
+                    let notification = self.link.callback(Msg::WsStatus);
+                    let ws_task = WebSocketService::connect_text(RELAY_URI, callback, notification).ok();
+
                     self.state = State::Chat(ChatState {
                         keys: Keys {
                             keypair,
@@ -79,10 +121,62 @@ impl Component for Root {
                             secret_key,
                         },
                         peer_id,
+                        ws_task,
+                        contacts: Vec::new(),
+                        selected_contact: None,
                     });
                 }
                 true
             }
+            Msg::WsStatus(WebSocketStatus::Opened) => {
+                if let State::Chat(state) = &mut self.state {
+                    let register = RelayFrame::Register {
+                        peer_id: state.peer_id.to_string(),
+                    };
+                    if let Some(ws_task) = &mut state.ws_task {
+                        if let Ok(text) = serde_json::to_string(&register) {
+                            ws_task.send(yew::format::Text(Ok(text)));
+                        }
+                    }
+                }
+                false
+            }
+            Msg::WsStatus(WebSocketStatus::Closed) | Msg::WsStatus(WebSocketStatus::Error) => {
+                if let State::Chat(state) = &mut self.state {
+                    state.ws_task = None;
+                }
+                true
+            }
+            Msg::WsReceived(Ok(text)) => {
+                self.handle_relay_frame(&text).msg_error(&self.link);
+                true
+            }
+            Msg::WsReceived(Err(err)) => {
+                ConsoleService::error(&format!("{}", err));
+                false
+            }
+            Msg::AddContact => {
+                if let State::Chat(state) = &mut self.state {
+                    let public_key = TextField::value("contact-public-key");
+                    match Self::add_contact(state, &public_key) {
+                        Ok(()) => TextField::set_value("contact-public-key", ""),
+                        Err(err) => ConsoleService::error(&format!("{}", err)),
+                    }
+                }
+                true
+            }
+            Msg::SelectContact(index) => {
+                if let State::Chat(state) = &mut self.state {
+                    state.selected_contact = Some(index);
+                }
+                true
+            }
+            Msg::SendMessage => {
+                if let State::Chat(state) = &mut self.state {
+                    Self::send_message(state).msg_error(&self.link);
+                }
+                true
+            }
             Msg::Error(err) => {
                 ConsoleService::error(&format!("{}", err));
                 true
@@ -125,6 +219,78 @@ impl Component for Root {
 }
 
 impl Root {
+    fn handle_relay_frame(&mut self, text: &str) -> Result<(), Error> {
+        let frame: RelayFrame = serde_json::from_str(text).context("Invalid relay frame")?;
+        let state = match &mut self.state {
+            State::Chat(state) => state,
+            State::SignIn => return Ok(()),
+        };
+
+        let (from, ciphertext) = match frame {
+            RelayFrame::Message { from, ciphertext, .. } => (from, ciphertext),
+            RelayFrame::Register { .. } => return Ok(()),
+        };
+
+        let contact_index = state
+            .contacts
+            .iter()
+            .position(|contact| contact.peer_id.to_string() == from)
+            .ok_or_else(|| anyhow!("Message from an unknown contact '{}'", from))?;
+
+        let contact = &mut state.contacts[contact_index];
+        let plaintext = crypto::decrypt(&contact.shared_key, &ciphertext)?;
+        contact.messages.push(ChatMessage {
+            outgoing: false,
+            text: plaintext,
+        });
+        Ok(())
+    }
+
+    fn add_contact(state: &mut ChatState, public_key: &str) -> Result<(), Error> {
+        let public_key_bytes = bs58::decode(public_key).into_vec().context("Decode contact public key error")?;
+        let ed25519_public_key =
+            libp2p_core::identity::ed25519::PublicKey::decode(&public_key_bytes).context("Invalid ed25519 public key")?;
+        let peer_id = PeerId::from(PublicKey::Ed25519(ed25519_public_key));
+
+        let shared_key = crypto::derive_shared_key(state.keys.keypair.secret().as_ref(), &public_key_bytes)?;
+
+        state.contacts.push(Contact {
+            peer_id,
+            public_key: public_key.to_string(),
+            shared_key,
+            messages: Vec::new(),
+        });
+        state.selected_contact = Some(state.contacts.len() - 1);
+        Ok(())
+    }
+
+    fn send_message(state: &mut ChatState) -> Result<(), Error> {
+        let index = state.selected_contact.ok_or_else(|| anyhow!("No contact selected"))?;
+        let text = TextField::value("message-composer");
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        let contact = &mut state.contacts[index];
+        let ciphertext = crypto::encrypt(&contact.shared_key, &text)?;
+
+        let frame = RelayFrame::Message {
+            to: contact.peer_id.to_string(),
+            from: state.peer_id.to_string(),
+            ciphertext,
+        };
+
+        let ws_task = state.ws_task.as_mut().ok_or_else(|| anyhow!("Not connected to the relay"))?;
+        ws_task.send(yew::format::Text(Ok(serde_json::to_string(&frame)?)));
+
+        contact.messages.push(ChatMessage {
+            outgoing: true,
+            text: text.clone(),
+        });
+        TextField::set_value("message-composer", "");
+        Ok(())
+    }
+
     fn view_sign_in(&self) -> Html {
         let generate_keypair_button = Button::new().id("generate-key-button").label("Generate").on_click(|_| {
             let keypair = Keypair::generate();
@@ -204,11 +370,67 @@ impl Root {
     }
 
     fn view_chat(&self, state: &ChatState) -> Html {
+        let contacts = List::simple_ul().items(
+            state
+                .contacts
+                .iter()
+                .enumerate()
+                .map(|(index, contact)| {
+                    let is_selected = state.selected_contact == Some(index);
+                    let onclick = self.link.callback(move |_| Msg::SelectContact(index));
+                    ListItem::simple().child(html! {
+                        <span class = { if is_selected { "contact contact--selected" } else { "contact" } } onclick = { onclick }>
+                            { &contact.public_key }
+                        </span>
+                    })
+                })
+                .collect(),
+        );
+
+        let add_contact_field = TextField::outlined().id("contact-public-key").label("Contact public key");
+        let add_contact_button = Button::new()
+            .label("Add contact")
+            .on_click(self.link.callback(|_| Msg::AddContact));
+
+        let transcript = state.selected_contact.and_then(|index| state.contacts.get(index)).map(|contact| {
+            html! {
+                <div class = "transcript">
+                    { for contact.messages.iter().map(|message| html! {
+                        <div class = { if message.outgoing { "message message--outgoing" } else { "message message--incoming" } }>
+                            { &message.text }
+                        </div>
+                    }) }
+                </div>
+            }
+        });
+
+        let composer = state.selected_contact.map(|_| {
+            html! {
+                <div class = "composer">
+                    { TextField::outlined().id("message-composer").class("expand").label("Message") }
+                    { Button::new().label("Send").on_click(self.link.callback(|_| Msg::SendMessage)) }
+                </div>
+            }
+        });
+
         html! {
             <>
                 <div>{ "Peer ID: " } { &state.peer_id }</div>
                 <div>{ "Public: " } { &state.keys.public_key }</div>
                 <div>{ "Secret: " } { &state.keys.secret_key }</div>
+                <div class = "chat-layout">
+                    <div class = "contacts">
+                        { contacts }
+                        <div class = "add-contact">
+                            { add_contact_field }
+                            { add_contact_button }
+                        </div>
+                    </div>
+                    <div class = "conversation">
+                        { for transcript }
+                        { for composer }
+                    </div>
+                </div>
             </>
         }
     }